@@ -3,12 +3,68 @@ use yewdux::prelude::*;
 use web_sys::HtmlInputElement;
 use berlewelch::*;
 
+// Which codec maps the original message text to `Gfe<P>` symbols: the restricted alphabet sized
+// to the chosen field, or arbitrary UTF-8 bytes packed via `bytes_to_c67`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Alphanumeric,
+    Bytes,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Alphanumeric
+    }
+}
+
+// The field prime codewords are evaluated over. `Gfe<P>` is a const generic, so picking the
+// field at runtime means dispatching by hand across a fixed set of supported primes rather than
+// letting the user enter an arbitrary one; 67/127/257 were chosen as the smallest primes that
+// respectively fit the original alphanumeric alphabet, comfortably exceed it, and cleanly exceed
+// a full byte's range (256 values plus one spare), per the request that motivated this.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldSize {
+    F67,
+    F127,
+    F257,
+}
+
+impl FieldSize {
+    fn prime(&self) -> i64 {
+        match self {
+            FieldSize::F67 => 67,
+            FieldSize::F127 => 127,
+            FieldSize::F257 => 257,
+        }
+    }
+}
+
+impl Default for FieldSize {
+    fn default() -> Self {
+        FieldSize::F67
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Store)]
 struct State {
     errors: i32,
     original: String,
     encoded: String,
     is_error: bool,
+    // indices into `encoded` that were found to be corrupted by the last successful decode
+    corrupted: Vec<usize>,
+    // number of `ERASURE_CHAR` symbols consumed as erasures by the last successful decode
+    erasures: usize,
+    mode: Mode,
+    // compress the message with the range coder before error-correction encoding
+    compress: bool,
+    // raw vs. post-compression symbol counts from the last encode, for display when `compress` is set
+    raw_symbols: usize,
+    compressed_symbols: usize,
+    // number of interleaved blocks the codeword is split across; 1 disables interleaving
+    blocks: i32,
+    // the field the codeword's symbols are evaluated over
+    field: FieldSize,
     // hack to force component rerendering to remove invalid characters from input elements even when no actual state was changed
     hack: bool,
 }
@@ -20,11 +76,38 @@ impl Default for State {
             original: String::new(),
             encoded: String::new(),
             is_error: false,
+            corrupted: Vec::new(),
+            erasures: 0,
+            mode: Mode::default(),
+            compress: false,
+            raw_symbols: 0,
+            compressed_symbols: 0,
+            blocks: 1,
+            field: FieldSize::default(),
             hack: false,
         }
     }
 }
 
+// Re-encodes `state.original` into `state.encoded` (and its raw/compressed symbol counts)
+// under the current errors/mode/compress/blocks/field settings, and resets the decode-result
+// fields that no longer apply once the encoded message has changed out from under them.
+fn recompute_encoded(state: &mut State) {
+    if state.original.is_empty() {
+        state.encoded = String::new();
+        state.raw_symbols = 0;
+        state.compressed_symbols = 0;
+    } else {
+        let (encoded, raw_symbols, compressed_symbols) = my_encode(state.errors as usize, &state.original, state.mode, state.compress, state.blocks as usize, state.field);
+        state.encoded = encoded;
+        state.raw_symbols = raw_symbols;
+        state.compressed_symbols = compressed_symbols;
+    }
+    state.is_error = false;
+    state.corrupted = Vec::new();
+    state.erasures = 0;
+}
+
 fn clamp(number: i32, min: i32, max: i32) -> i32 {
     if number < min {
         min
@@ -35,52 +118,657 @@ fn clamp(number: i32, min: i32, max: i32) -> i32 {
     }
 }
 
-fn is_valid_message(msg: &str) -> bool {
-    !msg.is_empty() && msg.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ',' || c == '/')
-}
-
-fn str_to_c67(msg: &str) -> Vec<Gfe<67>> {
-    msg.chars().map(|c| match c {
-        '_' => 62,
-        '-' => 63,
-        '.' => 64,
-        ',' => 65,
-        '/' => 66,
-        c if 'a' <= c && c <= 'z' => c as i64 - 'a' as i64,
-        c if 'A' <= c && c <= 'Z' => c as i64 - 'A' as i64 + 26,
-        c if '0' <= c && c <= '9' => c as i64 - '0' as i64 + 52,
-        _ => panic!("Unexpected character"),
-    }.into()).collect()
-}
-
-fn c67_to_str(msg: &[Gfe<67>]) -> String {
-    msg.iter().copied().map(|x| match *x {
-        62 => '_',
-        63 => '-',
-        64 => '.',
-        65 => ',',
-        66 => '/',
-        x if x < 26 => char::from_u32(x + 'a' as u32).unwrap(),
-        x if 26 <= x && x < 52 => char::from_u32(x - 26 + 'A' as u32).unwrap(),
-        x if 52 <= x && x < 62 => char::from_u32(x - 52 + '0' as u32).unwrap(),
-        _ => unreachable!(),
+// Sentinel character a user can paste into the encoded field to mark a symbol as a known
+// erasure instead of an unknown error; see `my_decode_with_erasures`.
+const ERASURE_CHAR: char = '?';
+
+// The characters making up the first 67 entries stay fixed, for backwards compatibility with the
+// original alphanumeric alphabet. Larger fields extend the table with more ASCII punctuation and
+// then further printable Unicode code points, in a fixed, deterministic order, so the mapping for
+// any given `prime` is just "however many of these characters fit".
+fn extra_alphabet_chars() -> impl Iterator<Item = char> {
+    let ascii_extra = "!\"#$%&'()*+:;<=>@[\\]^`{|}~ ".chars();
+    let extended = (0xA1u32..0x180).filter_map(char::from_u32).filter(|c| !c.is_control());
+    ascii_extra.chain(extended)
+}
+
+fn alphabet_for(prime: i64) -> Vec<char> {
+    let mut chars: Vec<char> = ('a'..='z').chain('A'..='Z').chain('0'..='9').collect();
+    chars.extend(['_', '-', '.', ',', '/']);
+    chars.extend(extra_alphabet_chars());
+    chars.truncate(prime as usize);
+    chars
+}
+
+fn is_valid_message_char(field: FieldSize, c: char) -> bool {
+    alphabet_for(field.prime()).contains(&c)
+}
+
+fn is_valid_message(field: FieldSize, msg: &str) -> bool {
+    !msg.is_empty() && msg.chars().all(|c| is_valid_message_char(field, c))
+}
+
+fn is_valid_encoded_message(field: FieldSize, msg: &str) -> bool {
+    !msg.is_empty() && msg.chars().all(|c| c == ERASURE_CHAR || is_valid_message_char(field, c))
+}
+
+fn str_to_c67<const P: i64>(msg: &str) -> Vec<Gfe<P>> {
+    let alphabet = alphabet_for(P);
+    msg.chars().map(|c| {
+        let idx = alphabet.iter().position(|&a| a == c).expect("invalid character") as i64;
+        idx.into()
+    }).collect()
+}
+
+fn c67_to_str<const P: i64>(msg: &[Gfe<P>]) -> String {
+    let alphabet = alphabet_for(P);
+    msg.iter().copied().map(|x| alphabet[*x as usize]).collect()
+}
+
+// Like `str_to_c67`, but treats `ERASURE_CHAR` as a known-bad symbol: its position is recorded
+// as an erasure rather than mapped through the alphabet.
+fn str_to_c67_with_erasures<const P: i64>(msg: &str) -> (Vec<Gfe<P>>, Vec<usize>) {
+    let alphabet = alphabet_for(P);
+    let mut erasures = Vec::new();
+    let symbols = msg.chars().enumerate().map(|(i, c)| {
+        if c == ERASURE_CHAR {
+            erasures.push(i);
+            0i64.into()
+        } else {
+            let idx = alphabet.iter().position(|&a| a == c).expect("invalid character") as i64;
+            idx.into()
+        }
+    }).collect();
+    (symbols, erasures)
+}
+
+// Packs arbitrary bytes into `Gfe<P>` symbols, the way a base64 engine packs bytes into 6-bit
+// groups: each fixed-size window of bytes is re-expressed as a fixed number of base-`P` digits
+// (padding the final, possibly-partial window with zero bytes), and a length prefix records the
+// exact byte count so `c67_to_bytes` can trim that padding back off on the way out. The digit
+// counts scale with the field: a bigger `P` packs the same byte window into fewer digits, down to
+// one digit per byte once `P` exceeds 256 (as GF(257) does).
+const BYTE_WINDOW: usize = 3;
+
+fn byte_window_digits(prime: i64) -> usize {
+    let target = 256u64.pow(BYTE_WINDOW as u32);
+    let mut digits = 0usize;
+    let mut capacity = 1u64;
+    while capacity < target {
+        capacity *= prime as u64;
+        digits += 1;
+    }
+    digits
+}
+
+fn length_digits(prime: i64) -> usize {
+    let min_capacity = 10_000_000u64; // far beyond what this demo needs to round-trip
+    let mut digits = 0usize;
+    let mut capacity = 1u64;
+    while capacity < min_capacity {
+        capacity *= prime as u64;
+        digits += 1;
+    }
+    digits.max(1)
+}
+
+fn digits_of(mut value: u64, digit_count: usize, prime: i64) -> Vec<i64> {
+    let mut digits = vec![0i64; digit_count];
+    for i in (0..digit_count).rev() {
+        digits[i] = (value % prime as u64) as i64;
+        value /= prime as u64;
+    }
+    digits
+}
+
+fn value_of<const P: i64>(digits: &[Gfe<P>]) -> u64 {
+    digits.iter().copied().fold(0u64, |acc, x| acc * P as u64 + *x as u64)
+}
+
+fn bytes_to_c67<const P: i64>(data: &[u8]) -> Vec<Gfe<P>> {
+    let length_digits = length_digits(P);
+    let window_digits = byte_window_digits(P);
+    let mut out: Vec<Gfe<P>> = digits_of(data.len() as u64, length_digits, P).into_iter().map(Into::into).collect();
+    for chunk in data.chunks(BYTE_WINDOW) {
+        let mut window = [0u8; BYTE_WINDOW];
+        window[..chunk.len()].copy_from_slice(chunk);
+        let value = window.iter().fold(0u64, |acc, &b| acc * 256 + b as u64);
+        out.extend(digits_of(value, window_digits, P).into_iter().map(Into::into));
+    }
+    out
+}
+
+fn c67_to_bytes<const P: i64>(symbols: &[Gfe<P>]) -> Result<Vec<u8>, ()> {
+    let length_digits = length_digits(P);
+    if symbols.len() < length_digits {
+        return Err(());
+    }
+    let window_digits = byte_window_digits(P);
+    let byte_len = value_of(&symbols[..length_digits]) as usize;
+    let mut out = Vec::new();
+    for chunk in symbols[length_digits..].chunks(window_digits) {
+        let value = value_of(chunk);
+        let value_bytes = value.to_be_bytes();
+        out.extend_from_slice(&value_bytes[value_bytes.len() - BYTE_WINDOW..]);
+    }
+    if byte_len > out.len() {
+        return Err(());
+    }
+    out.truncate(byte_len);
+    Ok(out)
+}
+
+// A static-frequency range coder used as an optional compression pre-pass ahead of `encode`, so
+// longer, redundant text fits under the same `2*errors` redundancy overhead. This is Subbotin's
+// carryless range coder: renormalization is forced whenever the range gets too small relative to
+// `RC_BOT` instead of tracking carry propagation explicitly, which costs a negligible amount of
+// compression ratio for much simpler code.
+const RC_TOP: u32 = 1 << 24;
+const RC_BOT: u32 = 1 << 16;
+
+struct RangeEncoder {
+    low: u32,
+    range: u32,
+    out: Vec<u8>,
+}
+
+impl RangeEncoder {
+    fn new() -> Self {
+        Self { low: 0, range: 0xFFFFFFFF, out: Vec::new() }
+    }
+
+    fn encode(&mut self, cum_freq: u32, freq: u32, tot_freq: u32) {
+        self.range /= tot_freq;
+        self.low = self.low.wrapping_add(cum_freq * self.range);
+        self.range *= freq;
+        while (self.low ^ self.low.wrapping_add(self.range)) < RC_TOP
+            || (self.range < RC_BOT && { self.range = self.low.wrapping_neg() & (RC_BOT - 1); true })
+        {
+            self.out.push((self.low >> 24) as u8);
+            self.low <<= 8;
+            self.range <<= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        for _ in 0..4 {
+            self.out.push((self.low >> 24) as u8);
+            self.low <<= 8;
+        }
+        self.out
+    }
+}
+
+struct RangeDecoder<'a> {
+    low: u32,
+    range: u32,
+    code: u32,
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RangeDecoder<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        let mut code = 0u32;
+        let mut pos = 0;
+        for _ in 0..4 {
+            code = (code << 8) | *input.get(pos).unwrap_or(&0) as u32;
+            pos += 1;
+        }
+        Self { low: 0, range: 0xFFFFFFFF, code, input, pos }
+    }
+
+    fn get_freq(&mut self, tot_freq: u32) -> u32 {
+        self.range /= tot_freq;
+        let freq = self.code.wrapping_sub(self.low) / self.range;
+        if freq >= tot_freq { tot_freq - 1 } else { freq }
+    }
+
+    fn decode(&mut self, cum_freq: u32, freq: u32) {
+        self.low = self.low.wrapping_add(cum_freq * self.range);
+        self.range *= freq;
+        while (self.low ^ self.low.wrapping_add(self.range)) < RC_TOP
+            || (self.range < RC_BOT && { self.range = self.low.wrapping_neg() & (RC_BOT - 1); true })
+        {
+            let byte = *self.input.get(self.pos).unwrap_or(&0);
+            self.pos += 1;
+            self.code = (self.code << 8) | byte as u32;
+            self.low <<= 8;
+            self.range <<= 8;
+        }
+    }
+}
+
+// Static frequency model over the field's symbol alphabet: every symbol gets a baseline weight so
+// none is ever impossible to encode, biased toward letters using rough English letter-frequency
+// figures (occurrences per thousand characters). Symbols beyond the first 62 (the alphanumeric
+// characters) just get the baseline weight.
+fn symbol_freqs(prime: i64) -> Vec<u32> {
+    const LETTER_FREQ: [u32; 26] = [
+        82, 15, 28, 43, 127, 22, 20, 61, 70, 2, 8, 40, 24, 67, 75, 19, 1, 60, 63, 91, 28, 10, 24, 2, 20, 1,
+    ];
+    let count = prime as usize;
+    let mut freqs = vec![4u32; count];
+    for i in 0..26.min(count) {
+        freqs[i] += LETTER_FREQ[i]; // lowercase a-z
+    }
+    for i in 0..26 {
+        if let Some(freq) = freqs.get_mut(26 + i) {
+            *freq += LETTER_FREQ[i] / 2; // uppercase, roughly half as common as lowercase
+        }
+    }
+    freqs
+}
+
+fn cumulative_freqs(freqs: &[u32]) -> (Vec<u32>, u32) {
+    let mut cum = vec![0u32; freqs.len() + 1];
+    for i in 0..freqs.len() {
+        cum[i + 1] = cum[i] + freqs[i];
+    }
+    let total = cum[freqs.len()];
+    (cum, total)
+}
+
+fn compress_symbols<const P: i64>(symbols: &[Gfe<P>]) -> Vec<u8> {
+    let (cum, total) = cumulative_freqs(&symbol_freqs(P));
+    let mut encoder = RangeEncoder::new();
+    for &s in symbols {
+        let sym = *s as usize;
+        encoder.encode(cum[sym], cum[sym + 1] - cum[sym], total);
+    }
+    let mut out = (symbols.len() as u32).to_be_bytes().to_vec();
+    out.extend(encoder.finish());
+    out
+}
+
+fn decompress_symbols<const P: i64>(data: &[u8]) -> Vec<Gfe<P>> {
+    let count = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+    let (cum, total) = cumulative_freqs(&symbol_freqs(P));
+    let mut decoder = RangeDecoder::new(&data[4..]);
+    (0..count).map(|_| {
+        let freq = decoder.get_freq(total);
+        let sym = (0..cum.len() - 1).find(|&i| freq < cum[i + 1]).unwrap();
+        decoder.decode(cum[sym], cum[sym + 1] - cum[sym]);
+        (sym as i64).into()
     }).collect()
 }
 
-fn my_encode(errors: usize, msg: &str) -> String {
-    let c67 = str_to_c67(msg);
-    let encoded = encode(errors, &c67);
-    c67_to_str(&encoded)
+fn msg_to_symbols<const P: i64>(mode: Mode, msg: &str) -> Vec<Gfe<P>> {
+    match mode {
+        Mode::Alphanumeric => str_to_c67::<P>(msg),
+        Mode::Bytes => bytes_to_c67::<P>(msg.as_bytes()),
+    }
+}
+
+fn symbols_to_msg<const P: i64>(mode: Mode, symbols: &[Gfe<P>]) -> Result<String, ()> {
+    match mode {
+        Mode::Alphanumeric => Ok(c67_to_str::<P>(symbols)),
+        Mode::Bytes => String::from_utf8(c67_to_bytes::<P>(symbols)?).map_err(|_| ()),
+    }
+}
+
+// Interleaving splits the symbol stream into `blocks` independently-encoded codewords and
+// transmits them column-wise, so a contiguous run of corruption up to `blocks * errors` symbols
+// long hits each block's own codeword at most `errors` times, instead of potentially piling every
+// corrupted symbol onto a single block. A small length header (protected like the rest of the
+// message, since it's just more message content) records the pre-padding symbol count so `decode_interleaved` can
+// trim the padding added to make every block the same size.
+const INTERLEAVE_LENGTH_DIGITS: usize = 2; // 67^2 symbols is far beyond what this demo needs
+
+fn encode_interleaved<const P: i64>(errors: usize, symbols: &[Gfe<P>], blocks: usize) -> Vec<Gfe<P>> {
+    if blocks <= 1 {
+        return encode(errors, symbols);
+    }
+    let mut padded: Vec<Gfe<P>> = digits_of(symbols.len() as u64, INTERLEAVE_LENGTH_DIGITS, P).into_iter().map(Into::into).collect();
+    padded.extend_from_slice(symbols);
+    let block_size = (padded.len() + blocks - 1) / blocks;
+    padded.resize(block_size * blocks, 0i64.into());
+
+    let codewords: Vec<Vec<Gfe<P>>> = padded.chunks(block_size).map(|chunk| encode(errors, chunk)).collect();
+    let codeword_len = block_size + 2 * errors;
+    let mut out = Vec::with_capacity(codeword_len * blocks);
+    for col in 0..codeword_len {
+        for codeword in &codewords {
+            out.push(codeword[col]);
+        }
+    }
+    out
+}
+
+// Encodes `msg` over the field `P`, returning the encoded message along with the raw and (if
+// `compress` is set) post-compression symbol counts, so the UI can show the space tradeoff
+// against the encoding's `2*errors` overhead.
+fn my_encode_over<const P: i64>(errors: usize, msg: &str, mode: Mode, compress: bool, blocks: usize) -> (String, usize, usize) {
+    let raw = msg_to_symbols::<P>(mode, msg);
+    let raw_count = raw.len();
+    let c67 = if compress {
+        bytes_to_c67::<P>(&compress_symbols::<P>(&raw))
+    } else {
+        raw
+    };
+    let symbol_count = c67.len();
+    let encoded = encode_interleaved::<P>(errors, &c67, blocks);
+    (c67_to_str::<P>(&encoded), raw_count, symbol_count)
+}
+
+// Dispatches `my_encode_over` to the `Gfe<P>` instantiation matching `field`. `Gfe`'s prime is a
+// const generic, so a runtime field choice can't flow straight through as a value; it has to pick
+// which of the three supported monomorphizations to call instead.
+fn my_encode(errors: usize, msg: &str, mode: Mode, compress: bool, blocks: usize, field: FieldSize) -> (String, usize, usize) {
+    match field {
+        FieldSize::F67 => my_encode_over::<67>(errors, msg, mode, compress, blocks),
+        FieldSize::F127 => my_encode_over::<127>(errors, msg, mode, compress, blocks),
+        FieldSize::F257 => my_encode_over::<257>(errors, msg, mode, compress, blocks),
+    }
 }
 
-fn my_decode(errors: usize, msg: &str) -> Result<String, ()> {
-    let mut c67 = str_to_c67(msg);
+// Decodes a received codeword's symbols, returning the recovered original symbols along with
+// the indices (into `symbols`) of the ones that were found to be corrupted. The corrupted
+// positions aren't tracked by the decoder itself, so they're recovered after the fact:
+// re-encoding the recovered message and diffing it against the received codeword yields exactly
+// the positions whose received symbol differs from the corrected codeword.
+fn decode_symbols<const P: i64>(errors: usize, symbols: &[Gfe<P>]) -> Result<(Vec<Gfe<P>>, Vec<usize>), ()> {
+    let mut c67 = symbols.to_vec();
     decode(errors, &mut c67)?;
-    Ok(c67_to_str(&c67[..c67.len() - 2 * errors]))
+    let original_len = c67.len() - 2 * errors;
+    let recoded = encode(errors, &c67[..original_len]);
+    let corrupted = symbols.iter().copied().zip(recoded.iter().copied())
+        .enumerate()
+        .filter(|(_, (a, b))| *a != *b)
+        .map(|(i, _)| i)
+        .collect();
+    Ok((c67[..original_len].to_vec(), corrupted))
+}
+
+fn my_decode_over<const P: i64>(errors: usize, msg: &str, mode: Mode, compress: bool, blocks: usize) -> Result<(String, Vec<usize>), ()> {
+    let symbols = str_to_c67::<P>(msg);
+    let (combined, corrupted, _) = decode_interleaved::<P>(errors, &symbols, &[], blocks)?;
+    let raw = if compress { decompress_symbols::<P>(&c67_to_bytes::<P>(&combined)?) } else { combined };
+    let decoded = symbols_to_msg::<P>(mode, &raw)?;
+    Ok((decoded, corrupted))
+}
+
+fn my_decode(errors: usize, msg: &str, mode: Mode, compress: bool, blocks: usize, field: FieldSize) -> Result<(String, Vec<usize>), ()> {
+    match field {
+        FieldSize::F67 => my_decode_over::<67>(errors, msg, mode, compress, blocks),
+        FieldSize::F127 => my_decode_over::<127>(errors, msg, mode, compress, blocks),
+        FieldSize::F257 => my_decode_over::<257>(errors, msg, mode, compress, blocks),
+    }
+}
+
+// Splits a received, column-interleaved symbol stream (plus the positions of any erasures within
+// it) back into `blocks` per-block codewords and locally-renumbered erasure positions, so each
+// block can be decoded independently with the existing single-block decoders.
+fn deinterleave_blocks<const P: i64>(symbols: &[Gfe<P>], erasures: &[usize], blocks: usize) -> Option<Vec<(Vec<Gfe<P>>, Vec<usize>)>> {
+    if blocks == 0 || symbols.len() % blocks != 0 {
+        return None;
+    }
+    let codeword_len = symbols.len() / blocks;
+    Some((0..blocks).map(|block| {
+        let block_symbols = (0..codeword_len).map(|col| symbols[col * blocks + block]).collect();
+        let block_erasures = erasures.iter()
+            .copied()
+            .filter(|&i| i % blocks == block)
+            .map(|i| i / blocks)
+            .collect();
+        (block_symbols, block_erasures)
+    }).collect())
+}
+
+// Decodes a column-interleaved codeword of `blocks` independently-encoded blocks (or a plain,
+// single codeword when `blocks <= 1`), returning the recovered symbols (with the interleaving
+// header stripped and padding trimmed), the corrupted positions (renumbered back into the
+// original interleaved stream), and the number of erasures consumed.
+fn decode_interleaved<const P: i64>(errors: usize, symbols: &[Gfe<P>], erasures: &[usize], blocks: usize) -> Result<(Vec<Gfe<P>>, Vec<usize>, usize), ()> {
+    if blocks <= 1 {
+        return if erasures.is_empty() {
+            let (decoded, corrupted) = decode_symbols(errors, symbols)?;
+            Ok((decoded, corrupted, 0))
+        } else {
+            decode_symbols_with_erasures(errors, symbols, erasures)
+        };
+    }
+
+    let per_block = deinterleave_blocks(symbols, erasures, blocks).ok_or(())?;
+    let mut decoded_blocks = Vec::with_capacity(blocks);
+    let mut corrupted = Vec::new();
+    let mut erasures_used = 0;
+    for (block, (block_symbols, block_erasures)) in per_block.into_iter().enumerate() {
+        let (decoded, block_corrupted, block_erasures_used) = if block_erasures.is_empty() {
+            let (decoded, block_corrupted) = decode_symbols(errors, &block_symbols)?;
+            (decoded, block_corrupted, 0)
+        } else {
+            decode_symbols_with_erasures(errors, &block_symbols, &block_erasures)?
+        };
+        corrupted.extend(block_corrupted.into_iter().map(|col| col * blocks + block));
+        erasures_used += block_erasures_used;
+        decoded_blocks.push(decoded);
+    }
+
+    let mut padded: Vec<Gfe<P>> = decoded_blocks.into_iter().flatten().collect();
+    if padded.len() < INTERLEAVE_LENGTH_DIGITS {
+        return Err(());
+    }
+    let real_len = value_of(&padded[..INTERLEAVE_LENGTH_DIGITS]) as usize;
+    let mut data = padded.split_off(INTERLEAVE_LENGTH_DIGITS);
+    if real_len > data.len() {
+        return Err(());
+    }
+    data.truncate(real_len);
+    Ok((data, corrupted, erasures_used))
+}
+
+// `berlewelch::decode` has no notion of erasures, so the erasure-aware path below re-implements
+// the Berlekamp-Welch key equation directly over GF(P) rather than extending the external crate.
+// Polynomial coefficient vectors are ordered lowest-degree first throughout. These helpers work
+// on raw `i64` residues rather than `Gfe<P>`, so the field prime is threaded through as an
+// ordinary argument instead of a const generic.
+
+fn gf_add(p: i64, a: i64, b: i64) -> i64 { (a + b).rem_euclid(p) }
+fn gf_sub(p: i64, a: i64, b: i64) -> i64 { (a - b).rem_euclid(p) }
+fn gf_mul(p: i64, a: i64, b: i64) -> i64 { (a * b).rem_euclid(p) }
+
+fn gf_pow(p: i64, a: i64, mut e: i64) -> i64 {
+    let mut base = a.rem_euclid(p);
+    let mut result = 1;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = gf_mul(p, result, base);
+        }
+        base = gf_mul(p, base, base);
+        e >>= 1;
+    }
+    result
+}
+
+// a^(p-2) is a's multiplicative inverse mod the prime p, by Fermat's little theorem.
+fn gf_inv(p: i64, a: i64) -> i64 {
+    gf_pow(p, a, p - 2)
+}
+
+fn poly_eval(p: i64, coeffs: &[i64], x: i64) -> i64 {
+    coeffs.iter().rev().fold(0, |acc, &c| gf_add(p, gf_mul(p, acc, x), c))
+}
+
+fn poly_mul(p: i64, a: &[i64], b: &[i64]) -> Vec<i64> {
+    let mut result = vec![0; a.len() + b.len() - 1];
+    for (i, &ai) in a.iter().enumerate() {
+        for (j, &bj) in b.iter().enumerate() {
+            result[i + j] = gf_add(p, result[i + j], gf_mul(p, ai, bj));
+        }
+    }
+    result
+}
+
+// Builds the erasure locator polynomial Lambda(x) = product_{i in erasures} (x - x_i), using the
+// evaluation point convention x_i = i shared with `berlewelch`'s codeword positions.
+fn locator_poly(p: i64, erasures: &[usize]) -> Vec<i64> {
+    erasures.iter().fold(vec![1], |acc, &i| poly_mul(p, &acc, &[gf_sub(p, 0, i as i64), 1]))
+}
+
+// Divides `num` by `den` over GF(p), returning (quotient, remainder).
+fn poly_divmod(p: i64, num: &[i64], den: &[i64]) -> (Vec<i64>, Vec<i64>) {
+    let den_deg = den.len() - 1;
+    let den_lead_inv = gf_inv(p, den[den_deg]);
+    let mut rem = num.to_vec();
+    let mut quot = vec![0; num.len().saturating_sub(den_deg)];
+    for i in (0..quot.len()).rev() {
+        let rem_deg = i + den_deg;
+        if rem_deg >= rem.len() {
+            continue;
+        }
+        let coeff = gf_mul(p, rem[rem_deg], den_lead_inv);
+        quot[i] = coeff;
+        for (j, &dj) in den.iter().enumerate() {
+            rem[i + j] = gf_sub(p, rem[i + j], gf_mul(p, coeff, dj));
+        }
+    }
+    while rem.len() > 1 && *rem.last().unwrap() == 0 {
+        rem.pop();
+    }
+    (quot, rem)
+}
+
+// Solves `a * x = b` over GF(p) via Gauss-Jordan elimination, tolerating systems that are
+// consistent but rank-deficient rather than assuming a unique, full-rank solution. This matters
+// because `decode_symbols_with_erasures` always sizes its system for the *budgeted* number of
+// errors, not however many are actually present; whenever the real error count is lower, the
+// system has a whole family of valid solutions instead of one. Columns with no remaining nonzero
+// pivot are left as free variables fixed at zero, which the Berlekamp-Welch theorem guarantees is
+// as good as any other member of that family once the result is run back through polynomial
+// division. Returns `None` only when the system is genuinely inconsistent, i.e. there's more
+// corruption than the equations budgeted for can account for.
+fn gf_solve(p: i64, mut a: Vec<Vec<i64>>, mut b: Vec<i64>) -> Option<Vec<i64>> {
+    let n = b.len();
+    let mut pivot_cols = Vec::new();
+    let mut row = 0;
+    for col in 0..n {
+        if row >= n {
+            break;
+        }
+        let pivot = match (row..n).find(|&r| a[r][col] != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        a.swap(row, pivot);
+        b.swap(row, pivot);
+        let inv = gf_inv(p, a[row][col]);
+        for j in col..n {
+            a[row][j] = gf_mul(p, a[row][j], inv);
+        }
+        b[row] = gf_mul(p, b[row], inv);
+        for r in 0..n {
+            if r == row || a[r][col] == 0 {
+                continue;
+            }
+            let factor = a[r][col];
+            for j in col..n {
+                a[r][j] = gf_sub(p, a[r][j], gf_mul(p, factor, a[row][j]));
+            }
+            b[r] = gf_sub(p, b[r], gf_mul(p, factor, b[row]));
+        }
+        pivot_cols.push(col);
+        row += 1;
+    }
+    if b[row..].iter().any(|&v| v != 0) {
+        return None;
+    }
+    let mut solution = vec![0i64; n];
+    for (i, &col) in pivot_cols.iter().enumerate() {
+        solution[col] = b[i];
+    }
+    Some(solution)
+}
+
+// Erasure-aware decode. Symbols at `erasures` positions are treated as known-bad: their
+// locations are folded into the erasure locator Lambda(x), so each erasure only costs one
+// redundancy symbol instead of two, allowing 2*t + f <= 2*errors for f erasures and t genuine
+// errors (rather than t <= errors alone). Returns the recovered symbols, the positions (into
+// `symbols`) of symbols that turned out to be genuinely corrupted, and the number of erasures
+// consumed.
+//
+// The key equation y_i * Lambda(x_i) * E'(x_i) = N(x_i) is solved over *all* n evaluation
+// points, not just the non-erased ones: at an erased position Lambda(x_i) is zero by
+// construction, so the equation degenerates to N(x_i) = 0 regardless of the garbage value
+// stored for y_i there. Those f equations are "free" and help pin down N(x)'s coefficients;
+// dropping them left the system short on equations (and thus rank-deficient) whenever any
+// symbol was actually erased.
+//
+// `unknowns` (= k + f + 2t) only equals n when f is even, since t = (2*errors - f) / 2 floors
+// away a half-step of correction power for odd f. When f is odd the system is over-determined
+// (unknowns < n): only the first `unknowns` equations are used to keep the matrix square, with
+// the rest left as redundancy that the corruption check afterwards still benefits from.
+fn decode_symbols_with_erasures<const P: i64>(errors: usize, symbols: &[Gfe<P>], erasures: &[usize]) -> Result<(Vec<Gfe<P>>, Vec<usize>, usize), ()> {
+    let n = symbols.len();
+    let f = erasures.len();
+    if n <= 2 * errors || f > 2 * errors {
+        return Err(());
+    }
+    let k = n - 2 * errors;
+    let t = (2 * errors - f) / 2;
+
+    let lambda = locator_poly(P, erasures);
+    let non_erased: Vec<usize> = (0..n).filter(|i| !erasures.contains(i)).collect();
+    let n_unknowns = k + f + t; // coefficients of N(x), which has degree < k + f + t
+    let unknowns = t + n_unknowns; // plus the non-leading coefficients of monic E'(x)
+
+    let mut a = Vec::with_capacity(unknowns);
+    let mut b = Vec::with_capacity(unknowns);
+    for i in 0..unknowns {
+        let x = i as i64;
+        let y = *symbols[i];
+        let lambda_x = poly_eval(P, &lambda, x);
+        let mut row = vec![0i64; unknowns];
+        let mut xp = 1i64;
+        for j in 0..t {
+            row[j] = gf_sub(P, 0, gf_mul(P, y, gf_mul(P, lambda_x, xp)));
+            xp = gf_mul(P, xp, x);
+        }
+        let mut xp = 1i64;
+        for m in 0..n_unknowns {
+            row[t + m] = xp;
+            xp = gf_mul(P, xp, x);
+        }
+        a.push(row);
+        b.push(gf_mul(P, y, gf_mul(P, lambda_x, gf_pow(P, x, t as i64))));
+    }
+
+    let solution = gf_solve(P, a, b).ok_or(())?;
+    let e_prime: Vec<i64> = solution[..t].iter().copied().chain(std::iter::once(1)).collect();
+    let n_poly = &solution[t..];
+    let e_poly = poly_mul(P, &lambda, &e_prime);
+    let (p_poly, remainder) = poly_divmod(P, n_poly, &e_poly);
+    if remainder.iter().any(|&c| c != 0) {
+        return Err(());
+    }
+
+    let decoded: Vec<Gfe<P>> = (0..k).map(|i| poly_eval(P, &p_poly, i as i64).into()).collect();
+    let recoded = encode(errors, &decoded);
+    let corrupted = non_erased.iter().copied()
+        .filter(|&i| *symbols[i] != *recoded[i])
+        .collect();
+
+    Ok((decoded, corrupted, f))
+}
+
+fn my_decode_with_erasures_over<const P: i64>(errors: usize, msg: &str, mode: Mode, compress: bool, blocks: usize) -> Result<(String, Vec<usize>, usize), ()> {
+    let (symbols, erasures) = str_to_c67_with_erasures::<P>(msg);
+    let (combined, corrupted, erasures_used) = decode_interleaved::<P>(errors, &symbols, &erasures, blocks)?;
+    let raw = if compress { decompress_symbols::<P>(&c67_to_bytes::<P>(&combined)?) } else { combined };
+    let decoded = symbols_to_msg::<P>(mode, &raw)?;
+    Ok((decoded, corrupted, erasures_used))
+}
+
+fn my_decode_with_erasures(errors: usize, msg: &str, mode: Mode, compress: bool, blocks: usize, field: FieldSize) -> Result<(String, Vec<usize>, usize), ()> {
+    match field {
+        FieldSize::F67 => my_decode_with_erasures_over::<67>(errors, msg, mode, compress, blocks),
+        FieldSize::F127 => my_decode_with_erasures_over::<127>(errors, msg, mode, compress, blocks),
+        FieldSize::F257 => my_decode_with_erasures_over::<257>(errors, msg, mode, compress, blocks),
+    }
 }
 
 #[function_component(App)]
-fn app() -> Html {    
+fn app() -> Html {
     html! {
         <div class="main-content">
             <h1>{ "Berlekamp-Welch Error Correction" }</h1>
@@ -98,47 +786,143 @@ fn app() -> Html {
                 will update to contain what is believed to be the corresponding original message, or an error message will appear if the original
                 message is known to be unrecoverable."
             } </p>
-            <p>{ "Messages may only contain characters a-z, A-Z, digits, underscores, dashes, periods, commas, and slashes." }</p>
+            <p>{ "In alphanumeric mode over the default GF(67) field, messages may only contain characters a-z, A-Z, digits, underscores, dashes, periods, commas, and slashes." }</p>
+            <p>{ "When a corrupted encoded message is successfully recovered, the characters that were found to be corrupted are highlighted below the encoded message field." }</p>
+            <p>{ "In the encoded message field, a '?' marks a symbol as a known erasure rather than an unknown error. Each erasure only
+                costs half as much of the error budget as a genuine error, so marking known-bad symbols lets more real corruption be corrected." }</p>
+            <p>{ "Byte mode lifts the character restriction, protecting arbitrary UTF-8 text (including emoji) by packing its raw bytes
+                into symbols instead of mapping characters directly." }</p>
+            <p>{ "The \"compress before encoding\" option range-codes the message against a static frequency model before it's
+                protected, so longer, redundant text can fit under the same error budget. The raw and compressed symbol counts
+                are shown so the tradeoff against the encoding's overhead is visible." }</p>
+            <p>{ "The \"Blocks\" setting splits the codeword across that many independently-encoded, column-interleaved blocks.
+                Ordinary corruption spread evenly through the message is unaffected, but a single contiguous burst of damage
+                is divided across the blocks instead of overwhelming one error budget, raising the tolerable burst length
+                from the max error count alone up to blocks times that count." }</p>
+            <p>{ "The \"Field Size\" setting picks how many evaluation points the codeword is built over. A larger field raises
+                the ceiling on how long a message (plus its error-correction overhead) can be, at the cost of an encoded form
+                drawn from a larger set of characters; alphanumeric mode's character table grows to match, and byte mode benefits most from GF(257), which
+                represents a full byte's range of values directly." }</p>
             <ErrorsInput />
+            <ModeInput />
+            <CompressInput />
+            <FieldSizeInput />
             <InputOutput />
         </div>
     }
 }
 
+#[function_component(CompressInput)]
+fn compress_input() -> Html {
+    let (state, dispatch) = use_store::<State>();
+
+    let on_input = dispatch.reduce_mut_callback_with(|state, evt: Event| {
+        let element = evt.target_dyn_into::<HtmlInputElement>().unwrap();
+        state.compress = element.checked();
+        recompute_encoded(state);
+    });
+
+    html! {
+        <div class="compress-input">
+            <label>
+                <input type="checkbox" checked={ state.compress } onchange={on_input} />
+                { " Compress before encoding" }
+            </label>
+        </div>
+    }
+}
+
+#[function_component(ModeInput)]
+fn mode_input() -> Html {
+    let (state, dispatch) = use_store::<State>();
+
+    let on_input = dispatch.reduce_mut_callback_with(|state, evt: Event| {
+        let element = evt.target_dyn_into::<HtmlInputElement>().unwrap();
+        state.mode = if element.checked() { Mode::Bytes } else { Mode::Alphanumeric };
+        if state.mode == Mode::Alphanumeric && !is_valid_message(state.field, &state.original) {
+            // switching out of byte mode can leave characters outside the alphanumeric alphabet
+            state.original = String::new();
+        }
+        recompute_encoded(state);
+    });
+
+    html! {
+        <div class="mode-input">
+            <label>
+                <input type="checkbox" checked={ state.mode == Mode::Bytes } onchange={on_input} />
+                { " Byte/UTF-8 mode" }
+            </label>
+        </div>
+    }
+}
+
+#[function_component(FieldSizeInput)]
+fn field_size_input() -> Html {
+    let (state, dispatch) = use_store::<State>();
+
+    let on_input = dispatch.reduce_mut_callback_with(|state, evt: Event| {
+        let element = evt.target_dyn_into::<HtmlInputElement>().unwrap();
+        state.field = match element.value().as_str() {
+            "127" => FieldSize::F127,
+            "257" => FieldSize::F257,
+            _ => FieldSize::F67,
+        };
+        if state.mode == Mode::Alphanumeric && !is_valid_message(state.field, &state.original) {
+            // shrinking the field can leave characters outside the new, smaller alphabet
+            state.original = String::new();
+        }
+        recompute_encoded(state);
+    });
+
+    html! {
+        <div class="field-size-input">
+            <label><h4>{ "Field Size:" }</h4></label>
+            <select onchange={on_input}>
+                <option value="67" selected={ state.field == FieldSize::F67 }>{ "GF(67)" }</option>
+                <option value="127" selected={ state.field == FieldSize::F127 }>{ "GF(127)" }</option>
+                <option value="257" selected={ state.field == FieldSize::F257 }>{ "GF(257)" }</option>
+            </select>
+        </div>
+    }
+}
+
 #[function_component(InputOutput)]
 fn input_output() -> Html {
     let (state, dispatch) = use_store::<State>();
-    
+
     let on_original_change = dispatch.reduce_mut_callback_with(|state, evt: InputEvent| {
         let target = evt.target_dyn_into::<HtmlInputElement>().unwrap();
         let new = target.value();
-        if !new.is_empty() && !is_valid_message(&new) {
+        if state.mode == Mode::Alphanumeric && !new.is_empty() && !is_valid_message(state.field, &new) {
             state.hack = !state.hack;
             return;
         }
-        let encoded = if new.is_empty() {
-            String::new()
-        } else {
-            my_encode(state.errors as usize, &new)
-        };
         state.original = new;
-        state.encoded = encoded;
-        state.is_error = false;
+        recompute_encoded(state);
     });
 
     let on_encoded_change = dispatch.reduce_mut_callback_with(|state, evt: InputEvent| {
         let target = evt.target_dyn_into::<HtmlInputElement>().unwrap();
         let new = target.value();
-        if !is_valid_message(&new) {
+        if !is_valid_encoded_message(state.field, &new) {
             state.hack = !state.hack;
             return;
         }
-        if let Ok(decoded) = my_decode(state.errors as usize, &new) {
+        let result = if new.contains(ERASURE_CHAR) {
+            my_decode_with_erasures(state.errors as usize, &new, state.mode, state.compress, state.blocks as usize, state.field)
+        } else {
+            my_decode(state.errors as usize, &new, state.mode, state.compress, state.blocks as usize, state.field).map(|(decoded, corrupted)| (decoded, corrupted, 0))
+        };
+        if let Ok((decoded, corrupted, erasures)) = result {
             state.original = decoded;
             state.is_error = false;
+            state.corrupted = corrupted;
+            state.erasures = erasures;
         } else {
             state.original = String::from("");
             state.is_error = true;
+            state.corrupted = Vec::new();
+            state.erasures = 0;
         }
         state.encoded = new;
     });
@@ -153,6 +937,31 @@ fn input_output() -> Html {
             } }
             <h4>{ "Encoded Message: " }</h4>
             <input class="input" type="text" value={ state.encoded.clone() } oninput={on_encoded_change}/>
+            { if !state.is_error && !state.corrupted.is_empty() {
+                html! {
+                    <div class="encoded-preview">
+                        { for state.encoded.chars().enumerate().map(|(i, c)| {
+                            if state.corrupted.contains(&i) {
+                                html! { <span class="corrupted-char">{ c.to_string() }</span> }
+                            } else {
+                                html! { <span>{ c.to_string() }</span> }
+                            }
+                        }) }
+                    </div>
+                }
+            } else {
+                html! {}
+            } }
+            { if !state.is_error && (state.erasures > 0 || !state.corrupted.is_empty()) {
+                html! { <p class="correction-summary">{ format!("Corrected {} error(s) and {} erasure(s).", state.corrupted.len(), state.erasures) }</p> }
+            } else {
+                html! {}
+            } }
+            { if state.compress && state.raw_symbols > 0 {
+                html! { <p class="compression-summary">{ format!("Compression turned {} raw symbols into {} before encoding.", state.raw_symbols, state.compressed_symbols) }</p> }
+            } else {
+                html! {}
+            } }
             { if state.is_error { html! { <h4>{ "Decoding Error" }</h4> } } else { html! {} } }
         </div>
     }
@@ -161,29 +970,41 @@ fn input_output() -> Html {
 #[function_component(ErrorsInput)]
 fn errors_input() -> Html {
     let (state, dispatch) = use_store::<State>();
-    
-    let on_input = dispatch.reduce_mut_callback_with(|state, evt: Event| {
+
+    let on_errors_input = dispatch.reduce_mut_callback_with(|state, evt: Event| {
         let element = evt.target_dyn_into::<HtmlInputElement>().unwrap();
         state.errors = element.value().parse().ok().map(|x| clamp(x, 1, 50)).unwrap_or(state.errors);
         state.hack = !state.hack;
-        
+
         if state.is_error {
             return;
         }
 
-        let encoded = if state.original.is_empty() {
-            String::new()
-        } else {
-            my_encode(state.errors as usize, &state.original)
-        };
-        state.encoded = encoded;
-        state.is_error = false;
+        recompute_encoded(state);
+    });
+
+    let on_blocks_input = dispatch.reduce_mut_callback_with(|state, evt: Event| {
+        let element = evt.target_dyn_into::<HtmlInputElement>().unwrap();
+        state.blocks = element.value().parse().ok().map(|x| clamp(x, 1, 16)).unwrap_or(state.blocks);
+        state.hack = !state.hack;
+
+        if state.is_error {
+            return;
+        }
+
+        recompute_encoded(state);
     });
 
     html! {
         <div class="errors-input">
             <label><h4>{ "Max Errors:" }</h4></label>
-            <input type="number" value={ state.errors.to_string() } min="1" max="50" onchange={on_input} />
+            <input type="number" value={ state.errors.to_string() } min="1" max="50" onchange={on_errors_input} />
+            <label><h4>{ "Blocks:" }</h4></label>
+            <input type="number" value={ state.blocks.to_string() } min="1" max="16" onchange={on_blocks_input} />
+            <p>{ format!(
+                "Splitting the codeword across {} interleaved block(s) lets a single contiguous burst of up to {} corrupted characters be corrected, instead of just {}.",
+                state.blocks, state.blocks * state.errors, state.errors,
+            ) }</p>
         </div>
     }
 }
@@ -191,4 +1012,4 @@ fn errors_input() -> Html {
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::start_app::<App>();
-}
\ No newline at end of file
+}